@@ -16,10 +16,21 @@
 
 use super::{
     util::{parse_descriptor, split_slice, ValidateAndByteswap, ValidationFunc},
-    DescriptorResult,
+    DescriptorError, DescriptorResult,
+};
+use alloc::{string::String, vec, vec::Vec};
+use avb_bindgen::{
+    avb_hashtree_descriptor_validate_and_byteswap, avb_sha1_final, avb_sha1_init,
+    avb_sha1_update, avb_sha256_final, avb_sha256_init, avb_sha256_update, avb_sha512_final,
+    avb_sha512_init, avb_sha512_update, AvbHashtreeDescriptor, AvbSHA1Ctx, AvbSHA256Ctx,
+    AvbSHA512Ctx,
+};
+use core::{
+    cell::{Cell, RefCell},
+    ffi::CStr,
+    ops::Range,
+    str::from_utf8,
 };
-use avb_bindgen::{avb_hashtree_descriptor_validate_and_byteswap, AvbHashtreeDescriptor};
-use core::{ffi::CStr, str::from_utf8};
 
 /// `AvbHashtreeDescriptorFlags`; see libavb docs for details.
 pub use avb_bindgen::AvbHashtreeDescriptorFlags as HashtreeDescriptorFlags;
@@ -117,6 +128,1000 @@ impl<'a> HashtreeDescriptor<'a> {
             flags: HashtreeDescriptorFlags(descriptor.header.flags),
         })
     }
+
+    /// Verify that `image` hashes, block by block, into the Merkle tree stored in `hash_tree`,
+    /// and that the tree's root matches `root_digest`.
+    ///
+    /// # Arguments
+    /// * `image`: the full hashed image, i.e. the first `image_size` bytes of the partition.
+    /// * `hash_tree`: the on-disk hash tree, i.e. `tree_size` bytes read from `tree_offset`.
+    ///
+    /// # Returns
+    /// `Ok(())` if `image` and `hash_tree` are consistent with `root_digest`, or
+    /// `DescriptorError` if not, including which block first failed to verify.
+    pub fn verify(&self, image: &[u8], hash_tree: &[u8]) -> DescriptorResult<()> {
+        let layout = self.tree_layout()?;
+        if hash_tree.len() as u64 != self.tree_size {
+            return Err(DescriptorError::InvalidSize);
+        }
+
+        // Level 0 holds the leaf digests of the image's data blocks; unlike the higher levels
+        // each stored digest is checked against one recomputed from `image` itself.
+        let level0 = &hash_tree[layout.level_range(0)];
+        for block_index in 0..layout.num_data_blocks {
+            let leaf = self.hash_data_block(image, block_index)?;
+            let want = layout.digest_at(level0, block_index)?;
+            if !ct_eq(leaf.as_slice(layout.digest_len), want) {
+                return Err(DescriptorError::HashtreeVerificationFailed(block_index));
+            }
+        }
+
+        // Each higher level is the hash, one hash block at a time, of the level below it.
+        for level in 1..layout.num_levels {
+            let below = &hash_tree[layout.level_range(level - 1)];
+            let this_level = &hash_tree[layout.level_range(level)];
+            for (hash_block_index, chunk) in
+                below.chunks(self.hash_block_size as usize).enumerate()
+            {
+                let mut hasher = Hasher::new(self.hash_algorithm)?;
+                hasher.update(chunk);
+                let digest = hasher.finish();
+                let want = layout.digest_at(this_level, hash_block_index as u64)?;
+                if !ct_eq(digest.as_slice(layout.digest_len), want) {
+                    return Err(DescriptorError::HashtreeVerificationFailed(
+                        hash_block_index as u64,
+                    ));
+                }
+            }
+        }
+
+        // The top level is exactly one hash block; hashing it yields the root.
+        let top = &hash_tree[layout.level_range(layout.num_levels - 1)];
+        let mut hasher = Hasher::new(self.hash_algorithm)?;
+        hasher.update(top);
+        let root = hasher.finish();
+        if !ct_eq(root.as_slice(layout.digest_len), self.root_digest) {
+            return Err(DescriptorError::HashtreeVerificationFailed(0));
+        }
+        Ok(())
+    }
+
+    /// Hashes the `block_index`-th `data_block_size` block of `image`, zero-padding it if it runs
+    /// past the end of `image`, the same way the on-disk hash tree was built.
+    fn hash_data_block(&self, image: &[u8], block_index: u64) -> DescriptorResult<Digest> {
+        hash_data_block(
+            self.hash_algorithm,
+            self.dm_verity_version,
+            self.salt,
+            self.data_block_size,
+            image,
+            block_index,
+        )
+    }
+
+    /// Computes the bottom-up level geometry of this descriptor's hash tree, validating that
+    /// `hash_algorithm` and `root_digest` are consistent with each other and that it fits within
+    /// `tree_size`.
+    fn tree_layout(&self) -> DescriptorResult<TreeLayout> {
+        let digest_len = hash_digest_len(self.hash_algorithm)?;
+        if self.root_digest.len() != digest_len {
+            return Err(DescriptorError::InvalidSize);
+        }
+        let layout = TreeLayout::new(
+            self.image_size,
+            self.data_block_size,
+            self.hash_block_size,
+            digest_len,
+        )?;
+        if layout.tree_size() != self.tree_size {
+            return Err(DescriptorError::InvalidSize);
+        }
+        Ok(layout)
+    }
+
+    /// Repairs `data` in place using the forward error correction parity in `fec`, the same way
+    /// the platform pairs dm-verity with libfec so a few bad blocks don't fail the whole
+    /// partition.
+    ///
+    /// # Arguments
+    /// * `data`: the bytes the FEC data protects, i.e. the image immediately followed by its hash
+    ///   tree.
+    /// * `fec`: the `fec_size` bytes of Reed-Solomon parity read from `fec_offset`.
+    ///
+    /// # Returns
+    /// The number of `data_block_size` blocks of `data` that were corrected, or
+    /// `DescriptorError::FecUncorrectable` if some codeword had more errors than its
+    /// `fec_num_roots / 2` correction budget.
+    pub fn repair(&self, data: &mut [u8], fec: &[u8]) -> DescriptorResult<usize> {
+        let nroots = self.fec_num_roots as usize;
+        if nroots == 0 || nroots >= rs::CODEWORD_LEN || nroots > rs::MAX_ROOTS {
+            return Err(DescriptorError::InvalidHeader);
+        }
+        if fec.len() as u64 != self.fec_size {
+            return Err(DescriptorError::InvalidSize);
+        }
+
+        // A single 4096-byte data block is interleaved one byte per codeword, so corruption
+        // concentrated in one block spreads thinly across many codewords instead of exhausting
+        // any single codeword's correction budget.
+        let k = rs::CODEWORD_LEN - nroots;
+        let num_rs_blocks = div_ceil(data.len() as u64, k as u64) as usize;
+        if fec.len() != num_rs_blocks * nroots {
+            return Err(DescriptorError::InvalidSize);
+        }
+
+        let gf = rs::Gf256::new();
+        let data_block_size = usize::max(self.data_block_size as usize, 1);
+        let num_data_blocks = (data.len() + data_block_size - 1) / data_block_size;
+        let mut corrected_blocks = vec![false; num_data_blocks];
+
+        for c in 0..num_rs_blocks {
+            let mut codeword = [0u8; rs::CODEWORD_LEN];
+            for (j, byte) in codeword[..k].iter_mut().enumerate() {
+                let pos = j * num_rs_blocks + c;
+                *byte = data.get(pos).copied().unwrap_or(0);
+            }
+            codeword[k..k + nroots].copy_from_slice(&fec[c * nroots..(c + 1) * nroots]);
+
+            if rs::decode(&gf, &mut codeword, nroots)? == 0 {
+                continue;
+            }
+            for (j, &corrected) in codeword[..k].iter().enumerate() {
+                let pos = j * num_rs_blocks + c;
+                if let Some(byte) = data.get_mut(pos) {
+                    if *byte != corrected {
+                        *byte = corrected;
+                        corrected_blocks[pos / data_block_size] = true;
+                    }
+                }
+            }
+        }
+
+        Ok(corrected_blocks.iter().filter(|&&touched| touched).count())
+    }
+
+    /// Serializes this descriptor to libavb's raw, big-endian on-disk layout: the
+    /// `AvbHashtreeDescriptor` header, followed by `partition_name`, `salt`, and `root_digest`,
+    /// zero-padded so the whole descriptor (including its 16-byte tag/length header) is a
+    /// multiple of 8 bytes. This is the inverse of [`HashtreeDescriptor::new`].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        /// `AVB_DESCRIPTOR_TAG_HASHTREE` from `avb_descriptor.h`.
+        const TAG_HASHTREE: u64 = 1;
+        const HASH_ALGORITHM_LEN: usize = 32;
+        const RESERVED_LEN: usize = 60;
+
+        // `hash_algorithm` is nul-terminated within its fixed-size field; every algorithm this
+        // crate knows about (see `hash_digest_len`) fits with room to spare.
+        let mut hash_algorithm = [0u8; HASH_ALGORITHM_LEN];
+        let name = self.hash_algorithm.as_bytes();
+        let name_len = name.len().min(HASH_ALGORITHM_LEN - 1);
+        hash_algorithm[..name_len].copy_from_slice(&name[..name_len]);
+
+        let mut body = Vec::new();
+        body.extend_from_slice(&self.dm_verity_version.to_be_bytes());
+        body.extend_from_slice(&self.image_size.to_be_bytes());
+        body.extend_from_slice(&self.tree_offset.to_be_bytes());
+        body.extend_from_slice(&self.tree_size.to_be_bytes());
+        body.extend_from_slice(&self.data_block_size.to_be_bytes());
+        body.extend_from_slice(&self.hash_block_size.to_be_bytes());
+        body.extend_from_slice(&self.fec_num_roots.to_be_bytes());
+        body.extend_from_slice(&self.fec_offset.to_be_bytes());
+        body.extend_from_slice(&self.fec_size.to_be_bytes());
+        body.extend_from_slice(&hash_algorithm);
+        body.extend_from_slice(&(self.partition_name.len() as u32).to_be_bytes());
+        body.extend_from_slice(&(self.salt.len() as u32).to_be_bytes());
+        body.extend_from_slice(&(self.root_digest.len() as u32).to_be_bytes());
+        body.extend_from_slice(&self.flags.0.to_be_bytes());
+        body.extend_from_slice(&[0u8; RESERVED_LEN]);
+
+        body.extend_from_slice(self.partition_name.as_bytes());
+        body.extend_from_slice(self.salt);
+        body.extend_from_slice(self.root_digest);
+        while (body.len() + 16) % 8 != 0 {
+            body.push(0);
+        }
+
+        let mut out = Vec::with_capacity(body.len() + 16);
+        out.extend_from_slice(&TAG_HASHTREE.to_be_bytes());
+        out.extend_from_slice(&(body.len() as u64).to_be_bytes());
+        out.extend_from_slice(&body);
+        out
+    }
+}
+
+/// Builds a dm-verity hash tree from scratch over raw image data, for signing tools that need
+/// to produce a [`HashtreeDescriptor`] rather than only inspect one parsed from a vbmeta image.
+pub struct HashtreeBuilder {
+    dm_verity_version: u32,
+    image_size: u64,
+    data_block_size: u32,
+    hash_block_size: u32,
+    hash_algorithm: String,
+    flags: u32,
+    partition_name: String,
+    salt: Vec<u8>,
+    root_digest: Vec<u8>,
+    tree: Vec<u8>,
+}
+
+impl HashtreeBuilder {
+    /// Computes the hash tree for `image` and its root digest.
+    ///
+    /// # Arguments
+    /// * `image`: the full image to hash, i.e. what will become the first `image_size` bytes of
+    ///   the partition.
+    /// * `dm_verity_version`: `1` to hash each block as `H(salt || block)`, `0` for
+    ///   `H(block || salt)`.
+    /// * `data_block_size`, `hash_block_size`, `hash_algorithm`, `salt`, `flags`,
+    ///   `partition_name`: as in [`HashtreeDescriptor`].
+    ///
+    /// # Returns
+    /// The builder, from which [`HashtreeBuilder::descriptor`] and [`HashtreeBuilder::tree`] can
+    /// be read, or `DescriptorError` if `hash_algorithm` is unknown or the block sizes are
+    /// invalid.
+    pub fn new(
+        image: &[u8],
+        dm_verity_version: u32,
+        data_block_size: u32,
+        hash_block_size: u32,
+        hash_algorithm: &str,
+        salt: &[u8],
+        flags: HashtreeDescriptorFlags,
+        partition_name: &str,
+    ) -> DescriptorResult<Self> {
+        let digest_len = hash_digest_len(hash_algorithm)?;
+        let image_size = image.len() as u64;
+        let layout = TreeLayout::new(image_size, data_block_size, hash_block_size, digest_len)?;
+        let mut tree = vec![0u8; layout.tree_size() as usize];
+
+        // Level 0 holds one leaf digest per `data_block_size` block of `image`; unlike the
+        // higher levels it's hashed from `image` itself rather than from the level below.
+        let level0_range = layout.level_range(0);
+        for block_index in 0..layout.num_data_blocks {
+            let digest = hash_data_block(
+                hash_algorithm,
+                dm_verity_version,
+                salt,
+                data_block_size,
+                image,
+                block_index,
+            )?;
+            let offset = level0_range.start + layout.digest_offset(block_index) as usize;
+            tree[offset..offset + digest_len].copy_from_slice(digest.as_slice(digest_len));
+        }
+
+        // Each higher level is the hash, one hash block at a time, of the level below it.
+        for level in 1..layout.num_levels {
+            let below = tree[layout.level_range(level - 1)].to_vec();
+            let this_level_range = layout.level_range(level);
+            for (hash_block_index, chunk) in below.chunks(hash_block_size as usize).enumerate() {
+                let mut hasher = Hasher::new(hash_algorithm)?;
+                hasher.update(chunk);
+                let digest = hasher.finish();
+                let offset =
+                    this_level_range.start + layout.digest_offset(hash_block_index as u64) as usize;
+                tree[offset..offset + digest_len].copy_from_slice(digest.as_slice(digest_len));
+            }
+        }
+
+        // The top level is exactly one hash block; hashing it yields the root.
+        let top = &tree[layout.level_range(layout.num_levels - 1)];
+        let mut hasher = Hasher::new(hash_algorithm)?;
+        hasher.update(top);
+        let root_digest = hasher.finish().as_slice(digest_len).to_vec();
+
+        Ok(Self {
+            dm_verity_version,
+            image_size,
+            data_block_size,
+            hash_block_size,
+            hash_algorithm: String::from(hash_algorithm),
+            flags: flags.0,
+            partition_name: String::from(partition_name),
+            salt: salt.to_vec(),
+            root_digest,
+            tree,
+        })
+    }
+
+    /// A [`HashtreeDescriptor`] describing the tree this builder computed, with `tree_offset`
+    /// set to `image_size` (the tree immediately follows the image, as dm-verity expects) and no
+    /// FEC data.
+    pub fn descriptor(&self) -> HashtreeDescriptor<'_> {
+        HashtreeDescriptor {
+            dm_verity_version: self.dm_verity_version,
+            image_size: self.image_size,
+            tree_offset: self.image_size,
+            tree_size: self.tree.len() as u64,
+            data_block_size: self.data_block_size,
+            hash_block_size: self.hash_block_size,
+            fec_num_roots: 0,
+            fec_offset: 0,
+            fec_size: 0,
+            hash_algorithm: &self.hash_algorithm,
+            flags: HashtreeDescriptorFlags(self.flags),
+            partition_name: &self.partition_name,
+            salt: &self.salt,
+            root_digest: &self.root_digest,
+        }
+    }
+
+    /// The on-disk hash tree bytes, i.e. what should be written at `tree_offset`.
+    pub fn tree(&self) -> &[u8] {
+        &self.tree
+    }
+}
+
+/// The bottom-up geometry of a dm-verity hash tree: how many hash blocks each level occupies.
+///
+/// Levels are numbered bottom-up: level 0 holds one digest per `data_block_size` block of the
+/// hashed image, level `n` holds one digest per hash block of level `n - 1`. The top level
+/// (`num_levels - 1`) always fits in exactly one hash block, and hashing that block yields the
+/// root digest. On disk the levels are stored top to bottom, the opposite of this numbering.
+#[derive(Debug, PartialEq, Eq)]
+struct TreeLayout {
+    num_data_blocks: u64,
+    /// Number of hash blocks occupied by each level, indexed bottom-up; only the first
+    /// `num_levels` entries are meaningful.
+    level_blocks: [u64; Self::MAX_LEVELS],
+    num_levels: usize,
+    digest_len: usize,
+    hash_block_size: u32,
+}
+
+impl TreeLayout {
+    /// Upper bound on tree depth: with a fan-out of at least 2 digests per hash block, any `u64`
+    /// count of data blocks collapses into a single top-level block within 64 levels.
+    const MAX_LEVELS: usize = 64;
+
+    fn new(
+        image_size: u64,
+        data_block_size: u32,
+        hash_block_size: u32,
+        digest_len: usize,
+    ) -> DescriptorResult<Self> {
+        if data_block_size == 0 || hash_block_size == 0 {
+            return Err(DescriptorError::InvalidHeader);
+        }
+        let digests_per_hash_block = (u64::from(hash_block_size) / digest_len as u64).max(1);
+        let num_data_blocks = div_ceil(image_size, u64::from(data_block_size)).max(1);
+
+        let mut level_blocks = [0u64; Self::MAX_LEVELS];
+        let mut num_levels = 0usize;
+        let mut count = num_data_blocks;
+        loop {
+            if num_levels == Self::MAX_LEVELS {
+                return Err(DescriptorError::InvalidSize);
+            }
+            let blocks = div_ceil(count, digests_per_hash_block);
+            level_blocks[num_levels] = blocks;
+            num_levels += 1;
+            if blocks <= 1 {
+                break;
+            }
+            count = blocks;
+        }
+
+        Ok(Self {
+            num_data_blocks,
+            level_blocks,
+            num_levels,
+            digest_len,
+            hash_block_size,
+        })
+    }
+
+    /// Total size, in bytes, of the on-disk hash tree this layout describes.
+    fn tree_size(&self) -> u64 {
+        self.level_blocks[..self.num_levels]
+            .iter()
+            .map(|blocks| blocks * u64::from(self.hash_block_size))
+            .sum()
+    }
+
+    /// Byte range of bottom-up `level` within the on-disk tree, which stores levels top to
+    /// bottom (so higher levels come first).
+    fn level_range(&self, level: usize) -> Range<usize> {
+        let preceding_blocks: u64 = self.level_blocks[level + 1..self.num_levels].iter().sum();
+        let start = preceding_blocks * u64::from(self.hash_block_size);
+        let len = self.level_blocks[level] * u64::from(self.hash_block_size);
+        start as usize..(start + len) as usize
+    }
+
+    /// Locates the `index`-th digest of this level's bytes, accounting for the zero padding at
+    /// the end of each hash block once its digests run out.
+    fn digest_at<'a>(&self, level: &'a [u8], index: u64) -> DescriptorResult<&'a [u8]> {
+        let offset = self.digest_offset(index) as usize;
+        level
+            .get(offset..offset + self.digest_len)
+            .ok_or(DescriptorError::InvalidSize)
+    }
+
+    /// Byte offset of the `index`-th digest within its level, accounting for the zero padding at
+    /// the end of each hash block once its digests run out.
+    fn digest_offset(&self, index: u64) -> u64 {
+        let block = index / self.digests_per_hash_block();
+        let offset_in_block = (index % self.digests_per_hash_block()) * self.digest_len as u64;
+        block * u64::from(self.hash_block_size) + offset_in_block
+    }
+
+    /// How many digests fit in one `hash_block_size` block, given `digest_len`.
+    fn digests_per_hash_block(&self) -> u64 {
+        (u64::from(self.hash_block_size) / self.digest_len as u64).max(1)
+    }
+}
+
+fn div_ceil(a: u64, b: u64) -> u64 {
+    (a + b - 1) / b
+}
+
+/// Supplies on-disk hash-tree bytes to a [`HashtreeVerifier`] on demand, so verifying one data
+/// block only reads the O(log n) hash blocks on its path to the root instead of requiring the
+/// whole tree in memory at once.
+pub trait HashBlockSource<'a> {
+    /// Returns `len` bytes of the hash tree at `offset`, both relative to `tree_offset`.
+    fn read(&mut self, offset: u64, len: usize) -> DescriptorResult<&'a [u8]>;
+}
+
+/// A `hash_tree` slice already fully in memory is itself a valid source.
+impl<'a> HashBlockSource<'a> for &'a [u8] {
+    fn read(&mut self, offset: u64, len: usize) -> DescriptorResult<&'a [u8]> {
+        let whole: &'a [u8] = *self;
+        whole
+            .get(offset as usize..offset as usize + len)
+            .ok_or(DescriptorError::InvalidSize)
+    }
+}
+
+impl<'a, F> HashBlockSource<'a> for F
+where
+    F: FnMut(u64, usize) -> DescriptorResult<&'a [u8]>,
+{
+    fn read(&mut self, offset: u64, len: usize) -> DescriptorResult<&'a [u8]> {
+        self(offset, len)
+    }
+}
+
+/// Incrementally verifies one `data_block_size` block of an image at a time against a
+/// [`HashtreeDescriptor`]'s hash tree, reading only the O(log n) hash blocks on that block's path
+/// to the root rather than requiring the whole image and tree in memory at once — e.g. to match
+/// the kernel's `dm-verity` read path, or to back the tree with a block device via a
+/// [`HashBlockSource`] closure instead of a borrowed slice.
+pub struct HashtreeVerifier<'a, S: HashBlockSource<'a>> {
+    layout: TreeLayout,
+    hash_algorithm: &'a str,
+    dm_verity_version: u32,
+    data_block_size: u32,
+    salt: &'a [u8],
+    root_digest: &'a [u8],
+    source: RefCell<S>,
+    /// Set once the top-level hash block has been checked against `root_digest`; later calls to
+    /// `verify_block` skip repeating that one comparison since the top-level block's content
+    /// never changes regardless of which leaf is being verified.
+    root_verified: Cell<bool>,
+}
+
+impl<'a, S: HashBlockSource<'a>> HashtreeVerifier<'a, S> {
+    /// Builds a verifier for `descriptor`'s hash tree, reading hash blocks on demand via
+    /// `source` — a `&[u8]` holding the whole on-disk tree, or any [`HashBlockSource`] (including
+    /// a plain `FnMut(offset, len) -> DescriptorResult<&[u8]>` closure) that fetches bytes from
+    /// wherever the tree actually lives.
+    pub fn new(descriptor: &HashtreeDescriptor<'a>, source: S) -> DescriptorResult<Self> {
+        let layout = descriptor.tree_layout()?;
+        Ok(Self {
+            layout,
+            hash_algorithm: descriptor.hash_algorithm,
+            dm_verity_version: descriptor.dm_verity_version,
+            data_block_size: descriptor.data_block_size,
+            salt: descriptor.salt,
+            root_digest: descriptor.root_digest,
+            source: RefCell::new(source),
+            root_verified: Cell::new(false),
+        })
+    }
+
+    /// The byte range, relative to `tree_offset`, of bottom-up level `level`'s on-disk bytes.
+    /// Exposed so callers backing the tree with a block device know which ranges to fetch;
+    /// `level` ranges over `0..self.num_levels()`.
+    pub fn level_range(&self, level: usize) -> Range<u64> {
+        let range = self.layout.level_range(level);
+        range.start as u64..range.end as u64
+    }
+
+    /// Number of levels in this hash tree, i.e. the valid range of `level` for
+    /// [`Self::level_range`].
+    pub fn num_levels(&self) -> usize {
+        self.layout.num_levels
+    }
+
+    /// Verifies that `block`, the `block_index`-th `data_block_size` block of the image, is
+    /// consistent with `root_digest` by walking its path from leaf to root in the hash tree.
+    ///
+    /// # Returns
+    /// `Ok(())` if `block` verifies, or `DescriptorError` if not: `InvalidSize` if `block_index`
+    /// is beyond `image_size / data_block_size`, or `HashtreeVerificationFailed` if a hash along
+    /// the path doesn't match.
+    pub fn verify_block(&self, block_index: u64, block: &[u8]) -> DescriptorResult<()> {
+        if block_index >= self.layout.num_data_blocks {
+            return Err(DescriptorError::InvalidSize);
+        }
+        let digest_len = self.layout.digest_len;
+        let digests_per_hash_block = self.layout.digests_per_hash_block();
+
+        let mut expected = hash_data_block(
+            self.hash_algorithm,
+            self.dm_verity_version,
+            self.salt,
+            self.data_block_size,
+            block,
+            0,
+        )?;
+        let mut index = block_index;
+
+        for level in 0..self.layout.num_levels {
+            let level_range = self.layout.level_range(level);
+            let hash_block_index = index / digests_per_hash_block;
+            let hash_block_start = (level_range.start
+                + hash_block_index as usize * self.layout.hash_block_size as usize)
+                as u64;
+            let hash_block = self
+                .source
+                .borrow_mut()
+                .read(hash_block_start, self.layout.hash_block_size as usize)?;
+
+            let offset_in_block = ((index % digests_per_hash_block) * digest_len as u64) as usize;
+            let want = hash_block
+                .get(offset_in_block..offset_in_block + digest_len)
+                .ok_or(DescriptorError::InvalidSize)?;
+            if !ct_eq(expected.as_slice(digest_len), want) {
+                return Err(DescriptorError::HashtreeVerificationFailed(block_index));
+            }
+
+            let is_top = level == self.layout.num_levels - 1;
+            if is_top && self.root_verified.get() {
+                return Ok(());
+            }
+
+            let mut hasher = Hasher::new(self.hash_algorithm)?;
+            hasher.update(hash_block);
+            expected = hasher.finish();
+
+            if is_top {
+                if !ct_eq(expected.as_slice(digest_len), self.root_digest) {
+                    return Err(DescriptorError::HashtreeVerificationFailed(block_index));
+                }
+                self.root_verified.set(true);
+                return Ok(());
+            }
+            index = hash_block_index;
+        }
+
+        Ok(())
+    }
+}
+
+/// Hashes the `block_index`-th `data_block_size` block of `image`, zero-padding it if it runs
+/// past the end of `image`, the same way the on-disk hash tree is built and verified.
+fn hash_data_block(
+    hash_algorithm: &str,
+    dm_verity_version: u32,
+    salt: &[u8],
+    data_block_size: u32,
+    image: &[u8],
+    block_index: u64,
+) -> DescriptorResult<Digest> {
+    let block_size = u64::from(data_block_size);
+    let start = block_index
+        .checked_mul(block_size)
+        .ok_or(DescriptorError::InvalidSize)?;
+    let end = start
+        .checked_add(block_size)
+        .ok_or(DescriptorError::InvalidSize)?;
+    let image_len = image.len() as u64;
+    let data = if start >= image_len {
+        &[][..]
+    } else {
+        &image[start as usize..end.min(image_len) as usize]
+    };
+    let padding = block_size - data.len() as u64;
+
+    let mut hasher = Hasher::new(hash_algorithm)?;
+    if dm_verity_version == 1 {
+        hasher.update(salt);
+        hasher.update(data);
+        hasher.update_zeroes(padding);
+    } else {
+        hasher.update(data);
+        hasher.update_zeroes(padding);
+        hasher.update(salt);
+    }
+    Ok(hasher.finish())
+}
+
+/// A digest buffer large enough to hold the output of any hash algorithm libavb supports.
+#[derive(Clone, Copy)]
+struct Digest {
+    bytes: [u8; Self::MAX_LEN],
+}
+
+impl Digest {
+    const MAX_LEN: usize = 64;
+
+    fn as_slice(&self, len: usize) -> &[u8] {
+        &self.bytes[..len]
+    }
+}
+
+/// Returns the digest size, in bytes, produced by the named hash algorithm.
+fn hash_digest_len(hash_algorithm: &str) -> DescriptorResult<usize> {
+    match hash_algorithm {
+        "sha1" => Ok(20),
+        "sha256" => Ok(32),
+        "sha512" => Ok(64),
+        _ => Err(DescriptorError::UnknownHashAlgorithm),
+    }
+}
+
+/// One of libavb's C hash contexts, used so hashtree verification matches the bootloader's
+/// hashing bit for bit.
+enum HasherCtx {
+    Sha1(AvbSHA1Ctx),
+    Sha256(AvbSHA256Ctx),
+    Sha512(AvbSHA512Ctx),
+}
+
+struct Hasher {
+    ctx: HasherCtx,
+}
+
+impl Hasher {
+    fn new(hash_algorithm: &str) -> DescriptorResult<Self> {
+        // SAFETY: `avb_shaN_init` only initializes the fields of a correctly-sized, otherwise
+        // uninitialized context; it performs no reads and cannot observe the zeroed state.
+        let ctx = unsafe {
+            match hash_algorithm {
+                "sha1" => {
+                    let mut ctx = core::mem::zeroed::<AvbSHA1Ctx>();
+                    avb_sha1_init(&mut ctx);
+                    HasherCtx::Sha1(ctx)
+                }
+                "sha256" => {
+                    let mut ctx = core::mem::zeroed::<AvbSHA256Ctx>();
+                    avb_sha256_init(&mut ctx);
+                    HasherCtx::Sha256(ctx)
+                }
+                "sha512" => {
+                    let mut ctx = core::mem::zeroed::<AvbSHA512Ctx>();
+                    avb_sha512_init(&mut ctx);
+                    HasherCtx::Sha512(ctx)
+                }
+                _ => return Err(DescriptorError::UnknownHashAlgorithm),
+            }
+        };
+        Ok(Self { ctx })
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        // SAFETY: `data` is a valid slice for its given length, and each `avb_shaN_update` only
+        // reads `data` and writes into the matching, correctly-typed context.
+        unsafe {
+            match &mut self.ctx {
+                HasherCtx::Sha1(ctx) => avb_sha1_update(ctx, data.as_ptr(), data.len()),
+                HasherCtx::Sha256(ctx) => avb_sha256_update(ctx, data.as_ptr(), data.len()),
+                HasherCtx::Sha512(ctx) => avb_sha512_update(ctx, data.as_ptr(), data.len()),
+            }
+        }
+    }
+
+    /// Feeds `count` zero bytes into the hash without requiring a `count`-sized buffer.
+    fn update_zeroes(&mut self, mut count: u64) {
+        const ZEROES: [u8; 64] = [0u8; 64];
+        while count > 0 {
+            let n = count.min(ZEROES.len() as u64) as usize;
+            self.update(&ZEROES[..n]);
+            count -= n as u64;
+        }
+    }
+
+    fn finish(self) -> Digest {
+        let mut bytes = [0u8; Digest::MAX_LEN];
+        // SAFETY: `avb_shaN_final` consumes the context by pointer and returns a pointer to
+        // `digest_size` bytes embedded within it, valid for the context's remaining lifetime.
+        unsafe {
+            match self.ctx {
+                HasherCtx::Sha1(mut ctx) => {
+                    let digest = avb_sha1_final(&mut ctx);
+                    bytes[..20].copy_from_slice(core::slice::from_raw_parts(digest, 20));
+                }
+                HasherCtx::Sha256(mut ctx) => {
+                    let digest = avb_sha256_final(&mut ctx);
+                    bytes[..32].copy_from_slice(core::slice::from_raw_parts(digest, 32));
+                }
+                HasherCtx::Sha512(mut ctx) => {
+                    let digest = avb_sha512_final(&mut ctx);
+                    bytes[..64].copy_from_slice(core::slice::from_raw_parts(digest, 64));
+                }
+            }
+        }
+        Digest { bytes }
+    }
+}
+
+/// Constant-time byte comparison, so a well-timed bit flip can't be used to probe a root digest
+/// one byte at a time.
+fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut diff = 0u8;
+    for (x, y) in a.iter().zip(b.iter()) {
+        diff |= x ^ y;
+    }
+    diff == 0
+}
+
+/// Reed-Solomon decoding over GF(2^8) (primitive polynomial `0x11d`, generator `2`), used to
+/// repair the image/hashtree bytes a [`HashtreeDescriptor`]'s FEC data protects.
+mod rs {
+    use super::{DescriptorError, DescriptorResult};
+
+    /// Every codeword libavb's FEC scheme produces is a full 255-byte GF(2^8) symbol block.
+    pub(super) const CODEWORD_LEN: usize = 255;
+
+    /// Generous cap on `fec_num_roots`: dm-verity images use small root counts (2 by default),
+    /// and this keeps every polynomial below fitting comfortably in a fixed-size stack buffer.
+    pub(super) const MAX_ROOTS: usize = 64;
+
+    /// Largest coefficient count any polynomial computed while decoding one codeword can reach:
+    /// the syndrome polynomial (`MAX_ROOTS + 1` coefficients) convolved with the errata locator
+    /// (`MAX_ROOTS / 2 + 1` coefficients).
+    const POLY_CAP: usize = MAX_ROOTS + MAX_ROOTS / 2 + 2;
+
+    /// GF(2^8) exponentiation/logarithm tables.
+    pub(super) struct Gf256 {
+        exp: [u8; 512],
+        log: [u8; 256],
+    }
+
+    impl Gf256 {
+        pub(super) fn new() -> Self {
+            let mut exp = [0u8; 512];
+            let mut log = [0u8; 256];
+            let mut x: u16 = 1;
+            for i in 0..255usize {
+                exp[i] = x as u8;
+                log[x as usize] = i as u8;
+                x <<= 1;
+                if x & 0x100 != 0 {
+                    x ^= 0x11d;
+                }
+            }
+            for i in 255..512 {
+                exp[i] = exp[i - 255];
+            }
+            Self { exp, log }
+        }
+
+        fn mul(&self, a: u8, b: u8) -> u8 {
+            if a == 0 || b == 0 {
+                return 0;
+            }
+            self.exp[self.log[a as usize] as usize + self.log[b as usize] as usize]
+        }
+
+        fn div(&self, a: u8, b: u8) -> u8 {
+            if a == 0 {
+                return 0;
+            }
+            let diff = self.log[a as usize] as i32 - self.log[b as usize] as i32;
+            self.exp[diff.rem_euclid(255) as usize]
+        }
+
+        /// Raises the field element `2` to `power`, wrapping `power` modulo the field's
+        /// multiplicative order (255) as needed for negative exponents.
+        pub(super) fn pow2(&self, power: i32) -> u8 {
+            self.exp[power.rem_euclid(255) as usize]
+        }
+
+        fn inverse(&self, a: u8) -> u8 {
+            self.exp[255 - self.log[a as usize] as usize]
+        }
+    }
+
+    /// A polynomial over GF(2^8), coefficients ordered highest-degree first (matching the order
+    /// bytes are read off the wire), backed by a fixed-capacity buffer sized for the largest
+    /// polynomial this module ever builds.
+    #[derive(Clone, Copy)]
+    struct Poly {
+        coeffs: [u8; POLY_CAP],
+        len: usize,
+    }
+
+    impl Poly {
+        fn from_coeffs(c: &[u8]) -> Self {
+            let mut coeffs = [0u8; POLY_CAP];
+            coeffs[..c.len()].copy_from_slice(c);
+            Self { coeffs, len: c.len() }
+        }
+
+        fn one() -> Self {
+            Self::from_coeffs(&[1])
+        }
+
+        fn as_slice(&self) -> &[u8] {
+            &self.coeffs[..self.len]
+        }
+
+        fn scale(&self, gf: &Gf256, factor: u8) -> Self {
+            let mut out = *self;
+            for c in &mut out.coeffs[..self.len] {
+                *c = gf.mul(*c, factor);
+            }
+            out
+        }
+
+        /// Appends a zero coefficient as the new lowest-degree term (i.e. multiplies by `x`,
+        /// keeping the existing coefficients as the higher-degree terms).
+        fn push_zero(&self) -> Self {
+            let mut out = *self;
+            out.coeffs[out.len] = 0;
+            out.len += 1;
+            out
+        }
+
+        fn add(&self, other: &Self) -> Self {
+            let len = self.len.max(other.len);
+            let mut coeffs = [0u8; POLY_CAP];
+            coeffs[len - self.len..len].copy_from_slice(self.as_slice());
+            for (i, &c) in other.as_slice().iter().enumerate() {
+                coeffs[len - other.len + i] ^= c;
+            }
+            Self { coeffs, len }
+        }
+
+        fn mul(&self, gf: &Gf256, other: &Self) -> Self {
+            let len = self.len + other.len - 1;
+            let mut coeffs = [0u8; POLY_CAP];
+            for (i, &a) in self.as_slice().iter().enumerate() {
+                if a == 0 {
+                    continue;
+                }
+                for (j, &b) in other.as_slice().iter().enumerate() {
+                    coeffs[i + j] ^= gf.mul(a, b);
+                }
+            }
+            Self { coeffs, len }
+        }
+
+        /// Evaluates the polynomial at `x` via Horner's method.
+        fn eval(&self, gf: &Gf256, x: u8) -> u8 {
+            let mut y = self.coeffs[0];
+            for &c in &self.as_slice()[1..] {
+                y = gf.mul(y, x) ^ c;
+            }
+            y
+        }
+
+        /// Drops leading zero coefficients, always leaving at least one.
+        fn trim(&self) -> Self {
+            let skip = self.as_slice()[..self.len - 1]
+                .iter()
+                .take_while(|&&c| c == 0)
+                .count();
+            Self::from_coeffs(&self.as_slice()[skip..])
+        }
+    }
+
+    /// Finds the error locator polynomial for `synd` (length `nsym + 1`, with a leading zero at
+    /// index 0) via the Berlekamp-Massey recurrence.
+    fn berlekamp_massey(gf: &Gf256, synd: &[u8], nsym: usize) -> DescriptorResult<Poly> {
+        let mut err_loc = Poly::one();
+        let mut old_loc = Poly::one();
+        for i in 0..nsym {
+            let mut delta = synd[i + 1];
+            for j in 1..err_loc.len {
+                delta ^= gf.mul(err_loc.as_slice()[err_loc.len - 1 - j], synd[i + 1 - j]);
+            }
+            old_loc = old_loc.push_zero();
+            if delta != 0 {
+                if old_loc.len > err_loc.len {
+                    let new_loc = old_loc.scale(gf, delta);
+                    old_loc = err_loc.scale(gf, gf.inverse(delta));
+                    err_loc = new_loc;
+                }
+                err_loc = err_loc.add(&old_loc.scale(gf, delta));
+            }
+        }
+        let err_loc = err_loc.trim();
+        if (err_loc.len - 1) * 2 > nsym {
+            return Err(DescriptorError::FecUncorrectable);
+        }
+        Ok(err_loc)
+    }
+
+    /// Decodes one 255-byte Reed-Solomon codeword in place, correcting up to `nsym / 2` byte
+    /// errors (the last `nsym` bytes being the parity symbols).
+    ///
+    /// # Returns
+    /// The number of corrected byte positions (`0` if the codeword had no errors), or
+    /// `DescriptorError::FecUncorrectable` if it had more errors than `nsym / 2`.
+    pub(super) fn decode(
+        gf: &Gf256,
+        codeword: &mut [u8; CODEWORD_LEN],
+        nsym: usize,
+    ) -> DescriptorResult<usize> {
+        // Syndromes at index 1..=nsym; the leading zero at index 0 lets the Berlekamp-Massey
+        // recurrence above index by `i + 1 - j` without ever underflowing.
+        let mut synd = [0u8; MAX_ROOTS + 1];
+        let mut any_nonzero = false;
+        for (i, s) in synd[1..=nsym].iter_mut().enumerate() {
+            let root = gf.pow2(i as i32);
+            let mut acc = 0u8;
+            for &b in codeword.iter() {
+                acc = gf.mul(acc, root) ^ b;
+            }
+            *s = acc;
+            any_nonzero |= acc != 0;
+        }
+        if !any_nonzero {
+            return Ok(0);
+        }
+
+        let err_loc = berlekamp_massey(gf, &synd[..=nsym], nsym)?;
+        let num_errors = err_loc.len - 1;
+
+        // Chien search: brute-force the error locator's roots over all 255 field elements. Each
+        // `i` for which `2^-i` zeroes `err_loc` names an erroneous byte at codeword position
+        // `CODEWORD_LEN - 1 - i`, with `X = 2^i` its error-locator value for Forney's algorithm.
+        let mut err_pos = [0usize; MAX_ROOTS];
+        let mut x_vals = [0u8; MAX_ROOTS];
+        let mut found = 0;
+        for i in 0..CODEWORD_LEN {
+            let root = gf.pow2(i as i32);
+            if err_loc.eval(gf, gf.inverse(root)) == 0 {
+                if found == num_errors {
+                    return Err(DescriptorError::FecUncorrectable);
+                }
+                err_pos[found] = CODEWORD_LEN - 1 - i;
+                x_vals[found] = root;
+                found += 1;
+            }
+        }
+        if found != num_errors {
+            return Err(DescriptorError::FecUncorrectable);
+        }
+
+        // Forney's algorithm: the errata locator (built from each error's `X` value) and the
+        // error evaluator polynomial give each error's magnitude.
+        let mut errata_loc = Poly::one();
+        for &x in &x_vals[..num_errors] {
+            errata_loc = errata_loc.mul(gf, &Poly::from_coeffs(&[x, 1]));
+        }
+        let mut synd_rev_buf = [0u8; MAX_ROOTS + 1];
+        for (i, &s) in synd[..=nsym].iter().rev().enumerate() {
+            synd_rev_buf[i] = s;
+        }
+        let synd_rev = Poly::from_coeffs(&synd_rev_buf[..=nsym]);
+        let full_eval = synd_rev.mul(gf, &errata_loc);
+        let err_eval = Poly::from_coeffs(&full_eval.as_slice()[full_eval.len - errata_loc.len..]);
+
+        for (&pos, &x) in err_pos[..num_errors].iter().zip(&x_vals[..num_errors]) {
+            let x_inv = gf.inverse(x);
+            let mut errata_loc_prime = 1u8;
+            for &other_x in &x_vals[..num_errors] {
+                if other_x != x {
+                    errata_loc_prime = gf.mul(errata_loc_prime, 1 ^ gf.mul(x_inv, other_x));
+                }
+            }
+            if errata_loc_prime == 0 {
+                return Err(DescriptorError::FecUncorrectable);
+            }
+            let y = gf.mul(x, err_eval.eval(gf, x_inv));
+            codeword[pos] ^= gf.div(y, errata_loc_prime);
+        }
+
+        Ok(num_errors)
+    }
 }
 
 #[cfg(test)]
@@ -170,4 +1175,305 @@ mod tests {
             DescriptorError::InvalidSize
         );
     }
+
+    /// A tiny hashtree built by hand: 10-byte image, 4-byte data blocks, 64-byte hash blocks,
+    /// SHA-256, `dm_verity_version` 1. See [`SMALL_HASHTREE_TREE`] for the tree shape.
+    fn small_hashtree_descriptor() -> HashtreeDescriptor<'static> {
+        HashtreeDescriptor {
+            dm_verity_version: 1,
+            image_size: 10,
+            tree_offset: 0,
+            tree_size: 192,
+            data_block_size: 4,
+            hash_block_size: 64,
+            fec_num_roots: 0,
+            fec_offset: 0,
+            fec_size: 0,
+            hash_algorithm: "sha256",
+            flags: HashtreeDescriptorFlags(0),
+            partition_name: "test",
+            salt: b"salt",
+            root_digest: &[
+                0xED, 0xC0, 0x21, 0x29, 0x47, 0x7D, 0x47, 0xD9, 0xFF, 0xD7, 0x05, 0x31, 0xBD,
+                0x46, 0xD1, 0xAF, 0x89, 0x16, 0xBA, 0xCE, 0xF8, 0xFB, 0x66, 0x1A, 0x3D, 0x60,
+                0x9B, 0xC2, 0x24, 0x3B, 0x4F, 0x8C,
+            ],
+        }
+    }
+
+    /// The on-disk hash tree for [`small_hashtree_descriptor`]: one 64-byte top level (two
+    /// digests of the blocks below), then one 128-byte bottom level (three leaf digests, the
+    /// last hash block zero-padded).
+    const SMALL_HASHTREE_TREE: &[u8] = &[
+        0xF3, 0x6F, 0x02, 0x1F, 0xAA, 0xD7, 0x86, 0x53, 0x3D, 0x45, 0x49, 0xFC, 0x83, 0x51, 0x50,
+        0xB0, 0x51, 0xE8, 0x1F, 0x5E, 0x52, 0xBC, 0xF6, 0x3D, 0xDF, 0x3E, 0x65, 0x3B, 0xB8, 0xB3,
+        0x3C, 0xC0, 0x37, 0x77, 0x9D, 0x3D, 0x46, 0x3B, 0x44, 0x35, 0x5E, 0x2A, 0xC0, 0x0B, 0xC5,
+        0x85, 0x31, 0x55, 0x3D, 0xD2, 0x42, 0xC9, 0xA6, 0xD1, 0xD1, 0x33, 0x7A, 0xDF, 0x1C, 0xBC,
+        0x07, 0x14, 0x38, 0x3E, 0x89, 0x1F, 0x2E, 0xD1, 0xDA, 0x60, 0x36, 0x28, 0x10, 0xF2, 0x9E,
+        0x34, 0xA2, 0x5F, 0x58, 0x01, 0x52, 0x63, 0x09, 0x5E, 0xAD, 0xFD, 0xAE, 0x5C, 0x80, 0x0A,
+        0x48, 0x00, 0x9C, 0x56, 0xFE, 0x2D, 0x7D, 0x9A, 0x4B, 0x02, 0x07, 0x26, 0x75, 0xF4, 0xCD,
+        0xCE, 0xFD, 0xA1, 0x11, 0xE0, 0xEE, 0x12, 0x7A, 0x30, 0x45, 0x60, 0x54, 0x7B, 0x7F, 0xBA,
+        0xC9, 0x5D, 0x9D, 0x5D, 0x12, 0x76, 0x82, 0x46, 0x2B, 0x8F, 0x26, 0xB4, 0x51, 0x75, 0xE9,
+        0x38, 0x44, 0x6B, 0xEC, 0x88, 0x99, 0x50, 0x77, 0xD7, 0x37, 0xE6, 0xD8, 0x85, 0x9B, 0x4A,
+        0xFD, 0x21, 0xCF, 0x5B, 0x31, 0x98, 0xD7, 0x9A, 0x76, 0xF8, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn verify_hashtree_success() {
+        let descriptor = small_hashtree_descriptor();
+        assert!(descriptor
+            .verify(b"0123456789", SMALL_HASHTREE_TREE)
+            .is_ok());
+    }
+
+    #[test]
+    fn verify_hashtree_wrong_image_byte_fails() {
+        let descriptor = small_hashtree_descriptor();
+        assert_eq!(
+            descriptor
+                .verify(b"012345678X", SMALL_HASHTREE_TREE)
+                .unwrap_err(),
+            DescriptorError::HashtreeVerificationFailed(2)
+        );
+    }
+
+    #[test]
+    fn verify_hashtree_wrong_tree_byte_fails() {
+        let descriptor = small_hashtree_descriptor();
+        let mut corrupt_tree = SMALL_HASHTREE_TREE.to_vec();
+        corrupt_tree[0] ^= 0xFF;
+        assert!(descriptor.verify(b"0123456789", &corrupt_tree).is_err());
+    }
+
+    #[test]
+    fn verify_hashtree_wrong_tree_size_fails() {
+        let descriptor = small_hashtree_descriptor();
+        assert_eq!(
+            descriptor
+                .verify(b"0123456789", &SMALL_HASHTREE_TREE[..100])
+                .unwrap_err(),
+            DescriptorError::InvalidSize
+        );
+    }
+
+    /// A descriptor whose FEC data protects a 600-byte image/hashtree blob (`FEC_TEST_*` below),
+    /// split into 3 interleaved 255-byte codewords (`k` = 247, 8 roots each).
+    fn fec_hashtree_descriptor() -> HashtreeDescriptor<'static> {
+        HashtreeDescriptor {
+            dm_verity_version: 1,
+            image_size: 600,
+            tree_offset: 600,
+            tree_size: 0,
+            data_block_size: 6,
+            hash_block_size: 64,
+            fec_num_roots: 8,
+            fec_offset: 600,
+            fec_size: FEC_TEST_FEC.len() as u64,
+            hash_algorithm: "sha256",
+            flags: HashtreeDescriptorFlags(0),
+            partition_name: "test",
+            salt: b"salt",
+            root_digest: &[],
+        }
+    }
+
+    const FEC_TEST_ORIGINAL_DATA: &[u8] = &[
+        0x39, 0x0C, 0x8C, 0x7D, 0x72, 0x47, 0x34, 0x2C, 0xD8, 0x10, 0x0F, 0x2F, 0x6F, 0x77, 0x0D, 0x65,
+        0xD6, 0x70, 0xE5, 0x8E, 0x03, 0x51, 0xD8, 0xAE, 0x8E, 0x4F, 0x6E, 0xAC, 0x34, 0x2F, 0xC2, 0x31,
+        0xB7, 0xB0, 0x87, 0x16, 0xEB, 0x3F, 0xC1, 0x28, 0x96, 0xB9, 0x62, 0x23, 0x17, 0x74, 0x94, 0x28,
+        0x77, 0x33, 0xC2, 0x8E, 0xE8, 0xBA, 0x53, 0xBD, 0xB5, 0x6B, 0x88, 0x24, 0x57, 0x7D, 0x53, 0xEC,
+        0xC2, 0x8A, 0x70, 0xA6, 0x1C, 0x75, 0x10, 0xA1, 0xCD, 0x89, 0x21, 0x6C, 0xA1, 0x6C, 0xFF, 0xCA,
+        0xEA, 0x49, 0x87, 0x47, 0x7E, 0x86, 0xDB, 0xCC, 0xB9, 0x70, 0x46, 0xFC, 0x2E, 0x18, 0x38, 0x4E,
+        0x51, 0xD8, 0x20, 0xC5, 0xC3, 0xEF, 0x80, 0x05, 0x3A, 0x88, 0xAE, 0x39, 0x96, 0xDE, 0x50, 0xE8,
+        0x01, 0x86, 0x5B, 0x36, 0x98, 0x65, 0x4E, 0xBF, 0x52, 0x00, 0xA5, 0xFA, 0x09, 0x39, 0xB9, 0x9D,
+        0x7A, 0x1D, 0x7B, 0x28, 0x2B, 0xF8, 0x23, 0x40, 0x41, 0xF3, 0x54, 0x87, 0xD8, 0x6C, 0x66, 0x9F,
+        0xCC, 0xBF, 0xE0, 0xE7, 0x3D, 0x7E, 0x73, 0x20, 0xAD, 0x0A, 0x75, 0x70, 0x03, 0x24, 0x1E, 0x75,
+        0x22, 0x10, 0xA9, 0x24, 0x79, 0x8E, 0xF8, 0x6D, 0x43, 0xF2, 0x7C, 0xF2, 0xD0, 0x61, 0x30, 0x31,
+        0xDC, 0xB5, 0xD8, 0xD2, 0xEF, 0x1B, 0x32, 0x1F, 0xCE, 0xAD, 0x37, 0x7F, 0x62, 0x61, 0xE5, 0x47,
+        0xD8, 0x5D, 0x8E, 0xEC, 0x7F, 0x26, 0xE2, 0x32, 0x19, 0x07, 0x2F, 0x79, 0x55, 0xD0, 0xF8, 0xF6,
+        0x6D, 0xCD, 0x1E, 0x54, 0xC2, 0x01, 0xC7, 0x87, 0xE8, 0x92, 0xD8, 0xF9, 0x4F, 0x61, 0x97, 0x6F,
+        0x1D, 0x1F, 0xA0, 0x1D, 0x19, 0xF4, 0x50, 0x1D, 0x29, 0x5F, 0x23, 0x22, 0x78, 0xCE, 0x3D, 0x7E,
+        0x14, 0x29, 0xD6, 0xA1, 0x85, 0x68, 0xA0, 0x7A, 0x87, 0xCA, 0x43, 0x99, 0xEA, 0xA1, 0x25, 0x04,
+        0xEA, 0x33, 0x25, 0x6D, 0x87, 0x43, 0xB2, 0x23, 0x7D, 0xBD, 0x91, 0x50, 0xE0, 0x9A, 0x04, 0x99,
+        0x35, 0x44, 0x87, 0x3B, 0x36, 0x4F, 0x8B, 0x90, 0x6B, 0xAF, 0x68, 0x87, 0xFA, 0x80, 0x1A, 0x2F,
+        0xD8, 0x8D, 0x16, 0x01, 0xAA, 0x42, 0x86, 0x52, 0xE2, 0xDA, 0x04, 0x39, 0x26, 0x4C, 0x12, 0xBD,
+        0x4B, 0xDC, 0x41, 0x15, 0x9D, 0xBA, 0x14, 0xB7, 0x6B, 0x7F, 0x34, 0xB5, 0xD0, 0x4F, 0x79, 0x53,
+        0x5A, 0xD3, 0x0C, 0x5B, 0xAA, 0xD2, 0x7F, 0x88, 0x51, 0x37, 0xC3, 0x13, 0xF0, 0x71, 0x66, 0xEB,
+        0xB3, 0x9C, 0x74, 0x72, 0x0C, 0x62, 0xCC, 0xA8, 0x8E, 0x23, 0x8E, 0xB3, 0xCC, 0xA9, 0x0E, 0x3B,
+        0x85, 0x5B, 0x87, 0x13, 0x37, 0xDE, 0xB0, 0xA0, 0xDF, 0x3B, 0xC5, 0x61, 0x82, 0x16, 0xDF, 0x00,
+        0x64, 0xBA, 0xDC, 0x23, 0xA9, 0xA0, 0x3F, 0x99, 0x9E, 0xD1, 0xA7, 0xCE, 0x97, 0x41, 0x62, 0xD7,
+        0xC2, 0x59, 0x9A, 0xCF, 0x00, 0x9B, 0x92, 0x6B, 0xDC, 0xA4, 0xEE, 0xE2, 0xE2, 0x6D, 0xF2, 0x56,
+        0x2B, 0x91, 0xAB, 0x2F, 0x78, 0x9E, 0x73, 0x65, 0x4B, 0x0C, 0x17, 0x7D, 0xF3, 0x25, 0xE9, 0xD4,
+        0x63, 0xC4, 0xFD, 0xCC, 0x7C, 0x4B, 0x02, 0x36, 0xD9, 0x70, 0x5A, 0xED, 0x19, 0x7F, 0x3E, 0xE9,
+        0x44, 0xED, 0xA2, 0xE2, 0xDA, 0xE4, 0x51, 0xF3, 0xE6, 0x84, 0x7E, 0x8D, 0xF8, 0x7A, 0x8C, 0xE1,
+        0x27, 0x92, 0x78, 0x8B, 0xAB, 0xA3, 0x29, 0x46, 0x4D, 0x76, 0xC4, 0x4E, 0x6D, 0x20, 0xD4, 0xD0,
+        0xA9, 0xEE, 0xD4, 0x1F, 0x69, 0xD7, 0xC7, 0x0A, 0xC2, 0xF4, 0x03, 0xB4, 0x98, 0xC7, 0xD6, 0x70,
+        0xF9, 0x70, 0x8B, 0xDF, 0xF8, 0x0E, 0xC7, 0xAC, 0xCF, 0x54, 0xEF, 0x41, 0x0D, 0xC9, 0x0D, 0x2A,
+        0xDB, 0x45, 0xEC, 0x5D, 0x19, 0x85, 0xC2, 0xA7, 0x6C, 0xE8, 0xA7, 0xAC, 0xC2, 0x8E, 0xD7, 0x81,
+        0x29, 0xF0, 0x09, 0x1A, 0xB3, 0x72, 0x23, 0x14, 0x0F, 0x7E, 0x66, 0x0A, 0x4E, 0x7A, 0x40, 0xF2,
+        0x3A, 0x6F, 0xEE, 0x83, 0xBC, 0x55, 0x3A, 0x53, 0x9F, 0x37, 0x0D, 0x9F, 0xC0, 0xCB, 0x65, 0x26,
+        0x7C, 0x34, 0x9A, 0x3D, 0x15, 0xB1, 0xDB, 0xBD, 0x23, 0xAE, 0x06, 0xD7, 0xFA, 0x36, 0xDD, 0xB9,
+        0xEB, 0x4E, 0xDE, 0x5A, 0x8A, 0xF7, 0xEE, 0xDF, 0x89, 0xA5, 0x7D, 0x2C, 0x8E, 0xE6, 0x7C, 0xED,
+        0xC2, 0xAC, 0x0E, 0xFD, 0xA6, 0x5D, 0xF9, 0x6C, 0xB5, 0x84, 0xAE, 0x8F, 0x8D, 0x05, 0x61, 0x2B,
+        0x7B, 0xD0, 0xFA, 0x7B, 0xF3, 0xFB, 0xE5, 0x08,
+    ];
+
+    const FEC_TEST_FEC: &[u8] = &[
+        0xEA, 0x35, 0x95, 0xB8, 0x48, 0x35, 0xBC, 0x68, 0xD8, 0x78, 0xA3, 0xDE, 0x4F, 0x33, 0x22, 0x69,
+        0xCC, 0x0B, 0xDA, 0x3D, 0x17, 0x18, 0x7D, 0x93,
+    ];
+
+    /// `FEC_TEST_ORIGINAL_DATA` with one data block (block index 50, bytes 300..306) corrupted by
+    /// a random XOR.
+    const FEC_TEST_CORRUPTED_DATA: &[u8] = &[
+        0x39, 0x0C, 0x8C, 0x7D, 0x72, 0x47, 0x34, 0x2C, 0xD8, 0x10, 0x0F, 0x2F, 0x6F, 0x77, 0x0D, 0x65,
+        0xD6, 0x70, 0xE5, 0x8E, 0x03, 0x51, 0xD8, 0xAE, 0x8E, 0x4F, 0x6E, 0xAC, 0x34, 0x2F, 0xC2, 0x31,
+        0xB7, 0xB0, 0x87, 0x16, 0xEB, 0x3F, 0xC1, 0x28, 0x96, 0xB9, 0x62, 0x23, 0x17, 0x74, 0x94, 0x28,
+        0x77, 0x33, 0xC2, 0x8E, 0xE8, 0xBA, 0x53, 0xBD, 0xB5, 0x6B, 0x88, 0x24, 0x57, 0x7D, 0x53, 0xEC,
+        0xC2, 0x8A, 0x70, 0xA6, 0x1C, 0x75, 0x10, 0xA1, 0xCD, 0x89, 0x21, 0x6C, 0xA1, 0x6C, 0xFF, 0xCA,
+        0xEA, 0x49, 0x87, 0x47, 0x7E, 0x86, 0xDB, 0xCC, 0xB9, 0x70, 0x46, 0xFC, 0x2E, 0x18, 0x38, 0x4E,
+        0x51, 0xD8, 0x20, 0xC5, 0xC3, 0xEF, 0x80, 0x05, 0x3A, 0x88, 0xAE, 0x39, 0x96, 0xDE, 0x50, 0xE8,
+        0x01, 0x86, 0x5B, 0x36, 0x98, 0x65, 0x4E, 0xBF, 0x52, 0x00, 0xA5, 0xFA, 0x09, 0x39, 0xB9, 0x9D,
+        0x7A, 0x1D, 0x7B, 0x28, 0x2B, 0xF8, 0x23, 0x40, 0x41, 0xF3, 0x54, 0x87, 0xD8, 0x6C, 0x66, 0x9F,
+        0xCC, 0xBF, 0xE0, 0xE7, 0x3D, 0x7E, 0x73, 0x20, 0xAD, 0x0A, 0x75, 0x70, 0x03, 0x24, 0x1E, 0x75,
+        0x22, 0x10, 0xA9, 0x24, 0x79, 0x8E, 0xF8, 0x6D, 0x43, 0xF2, 0x7C, 0xF2, 0xD0, 0x61, 0x30, 0x31,
+        0xDC, 0xB5, 0xD8, 0xD2, 0xEF, 0x1B, 0x32, 0x1F, 0xCE, 0xAD, 0x37, 0x7F, 0x62, 0x61, 0xE5, 0x47,
+        0xD8, 0x5D, 0x8E, 0xEC, 0x7F, 0x26, 0xE2, 0x32, 0x19, 0x07, 0x2F, 0x79, 0x55, 0xD0, 0xF8, 0xF6,
+        0x6D, 0xCD, 0x1E, 0x54, 0xC2, 0x01, 0xC7, 0x87, 0xE8, 0x92, 0xD8, 0xF9, 0x4F, 0x61, 0x97, 0x6F,
+        0x1D, 0x1F, 0xA0, 0x1D, 0x19, 0xF4, 0x50, 0x1D, 0x29, 0x5F, 0x23, 0x22, 0x78, 0xCE, 0x3D, 0x7E,
+        0x14, 0x29, 0xD6, 0xA1, 0x85, 0x68, 0xA0, 0x7A, 0x87, 0xCA, 0x43, 0x99, 0xEA, 0xA1, 0x25, 0x04,
+        0xEA, 0x33, 0x25, 0x6D, 0x87, 0x43, 0xB2, 0x23, 0x7D, 0xBD, 0x91, 0x50, 0xE0, 0x9A, 0x04, 0x99,
+        0x35, 0x44, 0x87, 0x3B, 0x36, 0x4F, 0x8B, 0x90, 0x6B, 0xAF, 0x68, 0x87, 0xFA, 0x80, 0x1A, 0x2F,
+        0xD8, 0x8D, 0x16, 0x01, 0xAA, 0x42, 0x86, 0x52, 0xE2, 0xDA, 0x04, 0x39, 0x3E, 0x00, 0x2B, 0xD5,
+        0xF9, 0xE3, 0x41, 0x15, 0x9D, 0xBA, 0x14, 0xB7, 0x6B, 0x7F, 0x34, 0xB5, 0xD0, 0x4F, 0x79, 0x53,
+        0x5A, 0xD3, 0x0C, 0x5B, 0xAA, 0xD2, 0x7F, 0x88, 0x51, 0x37, 0xC3, 0x13, 0xF0, 0x71, 0x66, 0xEB,
+        0xB3, 0x9C, 0x74, 0x72, 0x0C, 0x62, 0xCC, 0xA8, 0x8E, 0x23, 0x8E, 0xB3, 0xCC, 0xA9, 0x0E, 0x3B,
+        0x85, 0x5B, 0x87, 0x13, 0x37, 0xDE, 0xB0, 0xA0, 0xDF, 0x3B, 0xC5, 0x61, 0x82, 0x16, 0xDF, 0x00,
+        0x64, 0xBA, 0xDC, 0x23, 0xA9, 0xA0, 0x3F, 0x99, 0x9E, 0xD1, 0xA7, 0xCE, 0x97, 0x41, 0x62, 0xD7,
+        0xC2, 0x59, 0x9A, 0xCF, 0x00, 0x9B, 0x92, 0x6B, 0xDC, 0xA4, 0xEE, 0xE2, 0xE2, 0x6D, 0xF2, 0x56,
+        0x2B, 0x91, 0xAB, 0x2F, 0x78, 0x9E, 0x73, 0x65, 0x4B, 0x0C, 0x17, 0x7D, 0xF3, 0x25, 0xE9, 0xD4,
+        0x63, 0xC4, 0xFD, 0xCC, 0x7C, 0x4B, 0x02, 0x36, 0xD9, 0x70, 0x5A, 0xED, 0x19, 0x7F, 0x3E, 0xE9,
+        0x44, 0xED, 0xA2, 0xE2, 0xDA, 0xE4, 0x51, 0xF3, 0xE6, 0x84, 0x7E, 0x8D, 0xF8, 0x7A, 0x8C, 0xE1,
+        0x27, 0x92, 0x78, 0x8B, 0xAB, 0xA3, 0x29, 0x46, 0x4D, 0x76, 0xC4, 0x4E, 0x6D, 0x20, 0xD4, 0xD0,
+        0xA9, 0xEE, 0xD4, 0x1F, 0x69, 0xD7, 0xC7, 0x0A, 0xC2, 0xF4, 0x03, 0xB4, 0x98, 0xC7, 0xD6, 0x70,
+        0xF9, 0x70, 0x8B, 0xDF, 0xF8, 0x0E, 0xC7, 0xAC, 0xCF, 0x54, 0xEF, 0x41, 0x0D, 0xC9, 0x0D, 0x2A,
+        0xDB, 0x45, 0xEC, 0x5D, 0x19, 0x85, 0xC2, 0xA7, 0x6C, 0xE8, 0xA7, 0xAC, 0xC2, 0x8E, 0xD7, 0x81,
+        0x29, 0xF0, 0x09, 0x1A, 0xB3, 0x72, 0x23, 0x14, 0x0F, 0x7E, 0x66, 0x0A, 0x4E, 0x7A, 0x40, 0xF2,
+        0x3A, 0x6F, 0xEE, 0x83, 0xBC, 0x55, 0x3A, 0x53, 0x9F, 0x37, 0x0D, 0x9F, 0xC0, 0xCB, 0x65, 0x26,
+        0x7C, 0x34, 0x9A, 0x3D, 0x15, 0xB1, 0xDB, 0xBD, 0x23, 0xAE, 0x06, 0xD7, 0xFA, 0x36, 0xDD, 0xB9,
+        0xEB, 0x4E, 0xDE, 0x5A, 0x8A, 0xF7, 0xEE, 0xDF, 0x89, 0xA5, 0x7D, 0x2C, 0x8E, 0xE6, 0x7C, 0xED,
+        0xC2, 0xAC, 0x0E, 0xFD, 0xA6, 0x5D, 0xF9, 0x6C, 0xB5, 0x84, 0xAE, 0x8F, 0x8D, 0x05, 0x61, 0x2B,
+        0x7B, 0xD0, 0xFA, 0x7B, 0xF3, 0xFB, 0xE5, 0x08,
+    ];
+
+    #[test]
+    fn repair_fixes_corrupted_block() {
+        let descriptor = fec_hashtree_descriptor();
+        let mut data = FEC_TEST_CORRUPTED_DATA.to_vec();
+        assert_eq!(descriptor.repair(&mut data, FEC_TEST_FEC).unwrap(), 1);
+        assert_eq!(data, FEC_TEST_ORIGINAL_DATA);
+    }
+
+    #[test]
+    fn repair_leaves_uncorrupted_data_untouched() {
+        let descriptor = fec_hashtree_descriptor();
+        let mut data = FEC_TEST_ORIGINAL_DATA.to_vec();
+        assert_eq!(descriptor.repair(&mut data, FEC_TEST_FEC).unwrap(), 0);
+        assert_eq!(data, FEC_TEST_ORIGINAL_DATA);
+    }
+
+    #[test]
+    fn repair_rejects_wrong_fec_size() {
+        let descriptor = fec_hashtree_descriptor();
+        let mut data = FEC_TEST_CORRUPTED_DATA.to_vec();
+        assert_eq!(
+            descriptor.repair(&mut data, &FEC_TEST_FEC[..FEC_TEST_FEC.len() - 1]),
+            Err(DescriptorError::InvalidSize)
+        );
+    }
+
+    #[test]
+    fn build_hashtree_verifies_against_itself() {
+        let image = b"hello world, this is a small test image for the hashtree builder!";
+        let builder = HashtreeBuilder::new(
+            image,
+            1,
+            4,
+            64,
+            "sha256",
+            b"salt",
+            HashtreeDescriptorFlags(0),
+            "test",
+        )
+        .unwrap();
+
+        assert!(builder
+            .descriptor()
+            .verify(image, builder.tree())
+            .is_ok());
+    }
+
+    #[test]
+    fn build_and_serialize_hashtree_descriptor_round_trips() {
+        let image = b"hello world, this is a small test image for the hashtree builder!";
+        let builder = HashtreeBuilder::new(
+            image,
+            1,
+            4,
+            64,
+            "sha256",
+            b"salt",
+            HashtreeDescriptorFlags(0),
+            "test",
+        )
+        .unwrap();
+
+        let bytes = builder.descriptor().to_bytes();
+        let round_tripped = HashtreeDescriptor::new(&bytes).unwrap();
+        assert_eq!(round_tripped, builder.descriptor());
+    }
+
+    #[test]
+    fn verifier_verifies_each_block_from_slice() {
+        let descriptor = small_hashtree_descriptor();
+        let verifier = HashtreeVerifier::new(&descriptor, SMALL_HASHTREE_TREE).unwrap();
+        assert!(verifier.verify_block(0, b"0123").is_ok());
+        assert!(verifier.verify_block(1, b"4567").is_ok());
+        assert!(verifier.verify_block(2, b"89").is_ok());
+    }
+
+    #[test]
+    fn verifier_rejects_wrong_block() {
+        let descriptor = small_hashtree_descriptor();
+        let verifier = HashtreeVerifier::new(&descriptor, SMALL_HASHTREE_TREE).unwrap();
+        assert_eq!(
+            verifier.verify_block(0, b"XXXX").unwrap_err(),
+            DescriptorError::HashtreeVerificationFailed(0)
+        );
+    }
+
+    #[test]
+    fn verifier_rejects_out_of_range_block_index() {
+        let descriptor = small_hashtree_descriptor();
+        let verifier = HashtreeVerifier::new(&descriptor, SMALL_HASHTREE_TREE).unwrap();
+        assert_eq!(
+            verifier.verify_block(3, b"0000").unwrap_err(),
+            DescriptorError::InvalidSize
+        );
+    }
+
+    #[test]
+    fn verifier_works_with_callback_source() {
+        let descriptor = small_hashtree_descriptor();
+        let tree = SMALL_HASHTREE_TREE;
+        let verifier = HashtreeVerifier::new(&descriptor, |offset: u64, len: usize| {
+            tree.get(offset as usize..offset as usize + len)
+                .ok_or(DescriptorError::InvalidSize)
+        })
+        .unwrap();
+        assert!(verifier.verify_block(0, b"0123").is_ok());
+        assert!(verifier.verify_block(2, b"89").is_ok());
+    }
 }